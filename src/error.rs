@@ -1,58 +1,152 @@
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct Error(u32);
+#[derive(Clone, Copy)]
+pub struct Error(u32, i32);
 
 impl Error {
-    pub const OK: Error = Error(ffi::btrfs_util_error_BTRFS_UTIL_OK);
-    pub const STOP_ITERATION: Error = Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_STOP_ITERATION);
-    pub const NO_MEMORY: Error = Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_NO_MEMORY);
+    pub const OK: Error = Error(ffi::btrfs_util_error_BTRFS_UTIL_OK, 0);
+    pub const STOP_ITERATION: Error =
+        Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_STOP_ITERATION, 0);
+    pub const NO_MEMORY: Error = Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_NO_MEMORY, 0);
     pub const INVALID_ARGUMENT: Error =
-        Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_INVALID_ARGUMENT);
-    pub const NOT_BTRFS: Error = Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_NOT_BTRFS);
-    pub const NOT_SUBVOLUME: Error = Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_NOT_SUBVOLUME);
+        Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_INVALID_ARGUMENT, 0);
+    pub const NOT_BTRFS: Error = Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_NOT_BTRFS, 0);
+    pub const NOT_SUBVOLUME: Error = Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_NOT_SUBVOLUME, 0);
     pub const SUBVOLUME_NOT_FOUND: Error =
-        Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_SUBVOLUME_NOT_FOUND);
-    pub const OPEN_FAILED: Error = Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_OPEN_FAILED);
-    pub const RMDIR_FAILED: Error = Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_RMDIR_FAILED);
-    pub const UNLINK_FAILED: Error = Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_UNLINK_FAILED);
-    pub const STAT_FAILED: Error = Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_STAT_FAILED);
-    pub const STATFS_FAILED: Error = Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_STATFS_FAILED);
-    pub const SEARCH_FAILED: Error = Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_SEARCH_FAILED);
+        Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_SUBVOLUME_NOT_FOUND, 0);
+    pub const OPEN_FAILED: Error = Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_OPEN_FAILED, 0);
+    pub const RMDIR_FAILED: Error = Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_RMDIR_FAILED, 0);
+    pub const UNLINK_FAILED: Error = Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_UNLINK_FAILED, 0);
+    pub const STAT_FAILED: Error = Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_STAT_FAILED, 0);
+    pub const STATFS_FAILED: Error = Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_STATFS_FAILED, 0);
+    pub const SEARCH_FAILED: Error = Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_SEARCH_FAILED, 0);
     pub const INO_LOOKUP_FAILED: Error =
-        Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_INO_LOOKUP_FAILED);
+        Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_INO_LOOKUP_FAILED, 0);
     pub const SUBVOL_GETFLAGS_FAILED: Error =
-        Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_SUBVOL_GETFLAGS_FAILED);
+        Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_SUBVOL_GETFLAGS_FAILED, 0);
     pub const SUBVOL_SETFLAGS_FAILED: Error =
-        Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_SUBVOL_SETFLAGS_FAILED);
+        Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_SUBVOL_SETFLAGS_FAILED, 0);
     pub const SUBVOL_CREATE_FAILED: Error =
-        Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_SUBVOL_CREATE_FAILED);
+        Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_SUBVOL_CREATE_FAILED, 0);
     pub const SNAP_CREATE_FAILED: Error =
-        Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_SNAP_CREATE_FAILED);
+        Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_SNAP_CREATE_FAILED, 0);
     pub const SNAP_DESTROY_FAILED: Error =
-        Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_SNAP_DESTROY_FAILED);
+        Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_SNAP_DESTROY_FAILED, 0);
     pub const DEFAULT_SUBVOL_FAILED: Error =
-        Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_DEFAULT_SUBVOL_FAILED);
-    pub const SYNC_FAILED: Error = Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_SYNC_FAILED);
+        Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_DEFAULT_SUBVOL_FAILED, 0);
+    pub const SYNC_FAILED: Error = Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_SYNC_FAILED, 0);
     pub const START_SYNC_FAILED: Error =
-        Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_START_SYNC_FAILED);
+        Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_START_SYNC_FAILED, 0);
     pub const WAIT_SYNC_FAILED: Error =
-        Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_WAIT_SYNC_FAILED);
+        Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_WAIT_SYNC_FAILED, 0);
     pub const GET_SUBVOL_INFO_FAILED: Error =
-        Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_GET_SUBVOL_INFO_FAILED);
+        Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_GET_SUBVOL_INFO_FAILED, 0);
     pub const GET_SUBVOL_ROOTREF_FAILED: Error =
-        Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_GET_SUBVOL_ROOTREF_FAILED);
+        Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_GET_SUBVOL_ROOTREF_FAILED, 0);
     pub const INO_LOOKUP_USER_FAILED: Error =
-        Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_INO_LOOKUP_USER_FAILED);
-    pub const FS_INFO_FAILED: Error = Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_FS_INFO_FAILED);
+        Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_INO_LOOKUP_USER_FAILED, 0);
+    pub const FS_INFO_FAILED: Error =
+        Error(ffi::btrfs_util_error_BTRFS_UTIL_ERROR_FS_INFO_FAILED, 0);
 
     #[inline]
     pub fn is_unknown(&self) -> bool {
         self.0 > Self::FS_INFO_FAILED.0
     }
+
+    /// Creates an `Error` from a `btrfs_util_error` returned by a failing FFI call.
+    ///
+    /// For the `*_FAILED` variants, libbtrfsutil sets `errno` on the underlying syscall failure
+    /// right before returning, so it is captured here while it is still valid. The other variants
+    /// (e.g. [`Self::NOT_SUBVOLUME`], [`Self::INVALID_ARGUMENT`]) aren't backed by a failing
+    /// syscall, so `errno` would just be stale noise from something earlier and is left unset.
+    pub(crate) fn new(errcode: ffi::btrfs_util_error) -> Self {
+        let errno = if errcode as u32 >= Self::OPEN_FAILED.0 {
+            std::io::Error::last_os_error().raw_os_error().unwrap_or(0)
+        } else {
+            0
+        };
+        Error(errcode as u32, errno)
+    }
+
+    /// Returns the `errno` that libbtrfsutil set when this error occurred, if any.
+    ///
+    /// This lets callers distinguish e.g. `EPERM` from `ENOENT` on a failed
+    /// [`crate::delete_subvolume`]. Only meaningful for the `*_FAILED` variants; `None` otherwise.
+    pub fn errno(&self) -> Option<i32> {
+        if self.1 != 0 {
+            Some(self.1)
+        } else {
+            None
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self.0 {
+            c if c == Self::OK.0 => "OK",
+            c if c == Self::STOP_ITERATION.0 => "STOP_ITERATION",
+            c if c == Self::NO_MEMORY.0 => "NO_MEMORY",
+            c if c == Self::INVALID_ARGUMENT.0 => "INVALID_ARGUMENT",
+            c if c == Self::NOT_BTRFS.0 => "NOT_BTRFS",
+            c if c == Self::NOT_SUBVOLUME.0 => "NOT_SUBVOLUME",
+            c if c == Self::SUBVOLUME_NOT_FOUND.0 => "SUBVOLUME_NOT_FOUND",
+            c if c == Self::OPEN_FAILED.0 => "OPEN_FAILED",
+            c if c == Self::RMDIR_FAILED.0 => "RMDIR_FAILED",
+            c if c == Self::UNLINK_FAILED.0 => "UNLINK_FAILED",
+            c if c == Self::STAT_FAILED.0 => "STAT_FAILED",
+            c if c == Self::STATFS_FAILED.0 => "STATFS_FAILED",
+            c if c == Self::SEARCH_FAILED.0 => "SEARCH_FAILED",
+            c if c == Self::INO_LOOKUP_FAILED.0 => "INO_LOOKUP_FAILED",
+            c if c == Self::SUBVOL_GETFLAGS_FAILED.0 => "SUBVOL_GETFLAGS_FAILED",
+            c if c == Self::SUBVOL_SETFLAGS_FAILED.0 => "SUBVOL_SETFLAGS_FAILED",
+            c if c == Self::SUBVOL_CREATE_FAILED.0 => "SUBVOL_CREATE_FAILED",
+            c if c == Self::SNAP_CREATE_FAILED.0 => "SNAP_CREATE_FAILED",
+            c if c == Self::SNAP_DESTROY_FAILED.0 => "SNAP_DESTROY_FAILED",
+            c if c == Self::DEFAULT_SUBVOL_FAILED.0 => "DEFAULT_SUBVOL_FAILED",
+            c if c == Self::SYNC_FAILED.0 => "SYNC_FAILED",
+            c if c == Self::START_SYNC_FAILED.0 => "START_SYNC_FAILED",
+            c if c == Self::WAIT_SYNC_FAILED.0 => "WAIT_SYNC_FAILED",
+            c if c == Self::GET_SUBVOL_INFO_FAILED.0 => "GET_SUBVOL_INFO_FAILED",
+            c if c == Self::GET_SUBVOL_ROOTREF_FAILED.0 => "GET_SUBVOL_ROOTREF_FAILED",
+            c if c == Self::INO_LOOKUP_USER_FAILED.0 => "INO_LOOKUP_USER_FAILED",
+            c if c == Self::FS_INFO_FAILED.0 => "FS_INFO_FAILED",
+            _ => "UNKNOWN",
+        }
+    }
 }
 
+impl PartialEq for Error {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl Eq for Error {}
+
+impl std::hash::Hash for Error {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+
+impl std::fmt::Debug for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Error::{}", self.name())
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let msg = unsafe {
+            let cstr = ffi::btrfs_util_strerror(self.0);
+            std::ffi::CStr::from_ptr(cstr).to_string_lossy()
+        };
+        write!(f, "{}", msg)
+    }
+}
+
+impl std::error::Error for Error {}
+
 impl From<u32> for Error {
     fn from(errcode: u32) -> Self {
-        Error(errcode)
+        Error(errcode, 0)
     }
 }
 
@@ -60,4 +154,4 @@ impl From<Error> for u32 {
     fn from(err: Error) -> Self {
         err.0
     }
-}
\ No newline at end of file
+}
@@ -4,9 +4,12 @@ mod subvol;
 
 use bitflags::bitflags;
 use std::{
-    ffi::CString,
-    os::{raw::c_int, unix::prelude::OsStrExt},
-    path::Path,
+    ffi::{CStr, CString},
+    os::{
+        raw::c_int,
+        unix::prelude::{AsRawFd, OsStrExt, OsStringExt},
+    },
+    path::{Path, PathBuf},
 };
 
 pub use error::Error;
@@ -25,6 +28,16 @@ pub fn sync<P: AsRef<Path>>(path: P) -> Result<(), Error> {
     }
 }
 
+/// Like [`sync`], but takes an already-open file descriptor instead of a path.
+pub fn sync_fd<F: AsRawFd>(fd: &F) -> Result<(), Error> {
+    let errcode = unsafe { ffi::btrfs_util_sync_fd(fd.as_raw_fd()) };
+    if errcode == ffi::btrfs_util_error::BTRFS_UTIL_OK {
+        Ok(())
+    } else {
+        Err(Error::new(errcode))
+    }
+}
+
 /// Returns whether the given `path` is a Btrfs subvolume.
 pub fn is_subvolume<P: AsRef<Path>>(path: P) -> Result<bool, Error> {
     let cpath = CString::new(path.as_ref().as_os_str().as_bytes()).unwrap();
@@ -37,6 +50,21 @@ pub fn is_subvolume<P: AsRef<Path>>(path: P) -> Result<bool, Error> {
     }
 }
 
+/// Like [`is_subvolume`], but takes an already-open file descriptor instead of a path.
+///
+/// This avoids re-traversing and re-validating the path on every call, and lets a caller avoid a
+/// TOCTOU race between this check and a subsequent operation on the same path, by holding the
+/// directory open (e.g. with `O_PATH`) across both.
+pub fn is_subvolume_fd<F: AsRawFd>(fd: &F) -> Result<bool, Error> {
+    let errcode = unsafe { ffi::btrfs_util_is_subvolume_fd(fd.as_raw_fd()) };
+    match errcode {
+        ffi::btrfs_util_error::BTRFS_UTIL_OK => Ok(true),
+        ffi::btrfs_util_error::BTRFS_UTIL_ERROR_NOT_SUBVOLUME
+        | ffi::btrfs_util_error::BTRFS_UTIL_ERROR_NOT_BTRFS => Ok(false),
+        _ => Err(Error::new(errcode)),
+    }
+}
+
 /// Gets the ID of the subvolume containing the `path`.
 pub fn subvolume_id<P: AsRef<Path>>(path: P) -> Result<u64, Error> {
     let cpath = CString::new(path.as_ref().as_os_str().as_bytes()).unwrap();
@@ -49,6 +77,52 @@ pub fn subvolume_id<P: AsRef<Path>>(path: P) -> Result<u64, Error> {
     }
 }
 
+/// Like [`subvolume_id`], but takes an already-open file descriptor instead of a path.
+pub fn subvolume_id_fd<F: AsRawFd>(fd: &F) -> Result<u64, Error> {
+    let mut ret: u64 = 0;
+    let errcode = unsafe { ffi::btrfs_util_subvolume_id_fd(fd.as_raw_fd(), &mut ret) };
+    if errcode == ffi::btrfs_util_error::BTRFS_UTIL_OK {
+        Ok(ret)
+    } else {
+        Err(Error::new(errcode))
+    }
+}
+
+/// Gets the path of the subvolume with the given `id` on the filesystem containing `path`,
+/// relative to the filesystem root.
+///
+/// `id` of `0` means the subvolume containing `path`. This requires appropriate privilege
+/// (`CAP_SYS_ADMIN`).
+pub fn subvolume_path<P: AsRef<Path>>(path: P, id: u64) -> Result<PathBuf, Error> {
+    let cpath = CString::new(path.as_ref().as_os_str().as_bytes()).unwrap();
+    let mut cret: *mut std::os::raw::c_char = std::ptr::null_mut();
+    let errcode = unsafe { ffi::btrfs_util_subvolume_path(cpath.as_ptr(), id, &mut cret) };
+    if errcode != ffi::btrfs_util_error::BTRFS_UTIL_OK {
+        return Err(Error::new(errcode));
+    }
+    let ret = unsafe {
+        let bytes = CStr::from_ptr(cret).to_bytes().to_vec();
+        libc::free(cret as *mut _);
+        PathBuf::from(std::ffi::OsString::from_vec(bytes))
+    };
+    Ok(ret)
+}
+
+/// Like [`subvolume_path`], but takes an already-open file descriptor instead of a path.
+pub fn subvolume_path_fd<F: AsRawFd>(fd: &F, id: u64) -> Result<PathBuf, Error> {
+    let mut cret: *mut std::os::raw::c_char = std::ptr::null_mut();
+    let errcode = unsafe { ffi::btrfs_util_subvolume_path_fd(fd.as_raw_fd(), id, &mut cret) };
+    if errcode != ffi::btrfs_util_error::BTRFS_UTIL_OK {
+        return Err(Error::new(errcode));
+    }
+    let ret = unsafe {
+        let bytes = CStr::from_ptr(cret).to_bytes().to_vec();
+        libc::free(cret as *mut _);
+        PathBuf::from(std::ffi::OsString::from_vec(bytes))
+    };
+    Ok(ret)
+}
+
 /// Gets information about the subvolume with the given `id` on the filesystem containing the `path`.
 ///
 /// This requires appropriate privilege (`CAP_SYS_ADMIN`).
@@ -64,6 +138,18 @@ pub fn subvolume_info_with_id<P: AsRef<Path>>(path: P, id: u64) -> Result<Subvol
     Ok(out)
 }
 
+/// Like [`subvolume_info_with_id`], but takes an already-open file descriptor instead of a path.
+pub fn subvolume_info_with_id_fd<F: AsRawFd>(fd: &F, id: u64) -> Result<SubvolumeInfo, Error> {
+    let mut out = SubvolumeInfo::new();
+    unsafe {
+        let errcode = ffi::btrfs_util_subvolume_info_fd(fd.as_raw_fd(), id, out.as_ptr());
+        if errcode != ffi::btrfs_util_error::BTRFS_UTIL_OK {
+            return Err(Error::new(errcode));
+        }
+    }
+    Ok(out)
+}
+
 /// Gets information about the subvolume at the given `path`.
 ///
 /// This requires appropriate privilege (`CAP_SYS_ADMIN`) unless the kernel supports
@@ -72,6 +158,11 @@ pub fn subvolume_info<P: AsRef<Path>>(path: P) -> Result<SubvolumeInfo, Error> {
     subvolume_info_with_id(path, 0)
 }
 
+/// Like [`subvolume_info`], but takes an already-open file descriptor instead of a path.
+pub fn subvolume_info_fd<F: AsRawFd>(fd: &F) -> Result<SubvolumeInfo, Error> {
+    subvolume_info_with_id_fd(fd, 0)
+}
+
 /// Returns whether a subvolume is read-only.
 pub fn subvolume_read_only<P: AsRef<Path>>(path: P) -> Result<bool, Error> {
     let cpath = CString::new(path.as_ref().as_os_str().as_bytes()).unwrap();
@@ -85,6 +176,17 @@ pub fn subvolume_read_only<P: AsRef<Path>>(path: P) -> Result<bool, Error> {
     }
 }
 
+/// Like [`subvolume_read_only`], but takes an already-open file descriptor instead of a path.
+pub fn subvolume_read_only_fd<F: AsRawFd>(fd: &F) -> Result<bool, Error> {
+    let mut ret: bool = false;
+    let errcode = unsafe { ffi::btrfs_util_get_subvolume_read_only_fd(fd.as_raw_fd(), &mut ret) };
+    if errcode == ffi::btrfs_util_error::BTRFS_UTIL_OK {
+        Ok(ret)
+    } else {
+        Err(Error::new(errcode))
+    }
+}
+
 /// Set whether a subvolume is read-only.
 ///
 /// This requires appropriate privilege (CAP_SYS_ADMIN).
@@ -98,6 +200,108 @@ pub fn set_subvolume_read_only<P: AsRef<Path>>(path: P, read_only: bool) -> Resu
     }
 }
 
+/// Like [`set_subvolume_read_only`], but takes an already-open file descriptor instead of a path.
+///
+/// This requires appropriate privilege (CAP_SYS_ADMIN).
+pub fn set_subvolume_read_only_fd<F: AsRawFd>(fd: &F, read_only: bool) -> Result<(), Error> {
+    let errcode =
+        unsafe { ffi::btrfs_util_set_subvolume_read_only_fd(fd.as_raw_fd(), read_only) };
+    if errcode == ffi::btrfs_util_error::BTRFS_UTIL_OK {
+        Ok(())
+    } else {
+        Err(Error::new(errcode))
+    }
+}
+
+/// Returns the IDs of subvolumes that have been deleted but not yet cleaned up by the cleaner
+/// thread on the filesystem containing `path`.
+pub fn deleted_subvolumes<P: AsRef<Path>>(path: P) -> Result<Vec<u64>, Error> {
+    let cpath = CString::new(path.as_ref().as_os_str().as_bytes()).unwrap();
+    let mut ids: *mut u64 = std::ptr::null_mut();
+    let mut n: usize = 0;
+    let errcode =
+        unsafe { ffi::btrfs_util_deleted_subvolumes(cpath.as_ptr(), &mut ids, &mut n) };
+    if errcode != ffi::btrfs_util_error::BTRFS_UTIL_OK {
+        return Err(Error::new(errcode));
+    }
+    if n == 0 {
+        return Ok(Vec::new());
+    }
+    let ret = unsafe { std::slice::from_raw_parts(ids, n) }.to_vec();
+    unsafe { libc::free(ids as *mut _) };
+    Ok(ret)
+}
+
+/// Like [`deleted_subvolumes`], but takes an already-open file descriptor instead of a path.
+pub fn deleted_subvolumes_fd<F: AsRawFd>(fd: &F) -> Result<Vec<u64>, Error> {
+    let mut ids: *mut u64 = std::ptr::null_mut();
+    let mut n: usize = 0;
+    let errcode =
+        unsafe { ffi::btrfs_util_deleted_subvolumes_fd(fd.as_raw_fd(), &mut ids, &mut n) };
+    if errcode != ffi::btrfs_util_error::BTRFS_UTIL_OK {
+        return Err(Error::new(errcode));
+    }
+    if n == 0 {
+        return Ok(Vec::new());
+    }
+    let ret = unsafe { std::slice::from_raw_parts(ids, n) }.to_vec();
+    unsafe { libc::free(ids as *mut _) };
+    Ok(ret)
+}
+
+/// Gets the ID of the default subvolume of the filesystem containing `path`.
+///
+/// This requires appropriate privilege (`CAP_SYS_ADMIN`).
+pub fn get_default_subvolume<P: AsRef<Path>>(path: P) -> Result<u64, Error> {
+    let cpath = CString::new(path.as_ref().as_os_str().as_bytes()).unwrap();
+    let mut ret: u64 = 0;
+    let errcode = unsafe { ffi::btrfs_util_get_default_subvolume(cpath.as_ptr(), &mut ret) };
+    if errcode == ffi::btrfs_util_error::BTRFS_UTIL_OK {
+        Ok(ret)
+    } else {
+        Err(Error::new(errcode))
+    }
+}
+
+/// Like [`get_default_subvolume`], but takes an already-open file descriptor instead of a path.
+///
+/// This requires appropriate privilege (`CAP_SYS_ADMIN`).
+pub fn get_default_subvolume_fd<F: AsRawFd>(fd: &F) -> Result<u64, Error> {
+    let mut ret: u64 = 0;
+    let errcode = unsafe { ffi::btrfs_util_get_default_subvolume_fd(fd.as_raw_fd(), &mut ret) };
+    if errcode == ffi::btrfs_util_error::BTRFS_UTIL_OK {
+        Ok(ret)
+    } else {
+        Err(Error::new(errcode))
+    }
+}
+
+/// Sets the default subvolume of the filesystem containing `path` to `id`.
+///
+/// `id` of `0` selects the subvolume containing `path`. This requires appropriate privilege
+/// (`CAP_SYS_ADMIN`).
+pub fn set_default_subvolume<P: AsRef<Path>>(path: P, id: u64) -> Result<(), Error> {
+    let cpath = CString::new(path.as_ref().as_os_str().as_bytes()).unwrap();
+    let errcode = unsafe { ffi::btrfs_util_set_default_subvolume(cpath.as_ptr(), id) };
+    if errcode == ffi::btrfs_util_error::BTRFS_UTIL_OK {
+        Ok(())
+    } else {
+        Err(Error::new(errcode))
+    }
+}
+
+/// Like [`set_default_subvolume`], but takes an already-open file descriptor instead of a path.
+///
+/// This requires appropriate privilege (`CAP_SYS_ADMIN`).
+pub fn set_default_subvolume_fd<F: AsRawFd>(fd: &F, id: u64) -> Result<(), Error> {
+    let errcode = unsafe { ffi::btrfs_util_set_default_subvolume_fd(fd.as_raw_fd(), id) };
+    if errcode == ffi::btrfs_util_error::BTRFS_UTIL_OK {
+        Ok(())
+    } else {
+        Err(Error::new(errcode))
+    }
+}
+
 bitflags! {
     #[derive(Default)]
     pub struct DeleteSubvolumeFlags: c_int {
@@ -118,6 +322,109 @@ pub fn delete_subvolume<P: AsRef<Path>>(path: P, flags: DeleteSubvolumeFlags) ->
     Ok(())
 }
 
+/// Like [`delete_subvolume`], but takes an already-open file descriptor instead of a path.
+pub fn delete_subvolume_fd<F: AsRawFd>(fd: &F, flags: DeleteSubvolumeFlags) -> Result<(), Error> {
+    let cflags = flags.bits();
+    unsafe {
+        let errcode = ffi::btrfs_util_delete_subvolume_fd(fd.as_raw_fd(), cflags);
+        if errcode != ffi::btrfs_util_error::BTRFS_UTIL_OK {
+            return Err(Error::new(errcode));
+        }
+    }
+    Ok(())
+}
+
+/// Deletes a subvolume or snapshot, returning a transaction ID that can be passed to
+/// [`wait_sync`] to block until the deletion is committed to disk.
+///
+/// Unlike [`create_subvolume_async`] and [`create_snapshot_async`], `btrfs_util_delete_subvolume`
+/// has no asynchronous out-param of its own: deleting the dirent is synchronous, and the only
+/// asynchronous part is the cleaner thread freeing the extents afterwards, which
+/// [`deleted_subvolumes`] already exposes. This helper just starts a sync right after deleting,
+/// without waiting for it, so a caller that batches many deletions still starts one commit per
+/// deletion but can block only once, by calling [`wait_sync`] at the end with the last returned
+/// transid.
+pub fn delete_subvolume_async<P: AsRef<Path>>(
+    path: P,
+    flags: DeleteSubvolumeFlags,
+) -> Result<u64, Error> {
+    delete_subvolume(path.as_ref(), flags)?;
+    start_sync(path)
+}
+
+/// Like [`delete_subvolume_async`], but takes an already-open file descriptor instead of a path.
+pub fn delete_subvolume_async_fd<F: AsRawFd>(
+    fd: &F,
+    flags: DeleteSubvolumeFlags,
+) -> Result<u64, Error> {
+    let raw_fd = fd.as_raw_fd();
+    let cflags = flags.bits();
+    unsafe {
+        let errcode = ffi::btrfs_util_delete_subvolume_fd(raw_fd, cflags);
+        if errcode != ffi::btrfs_util_error::BTRFS_UTIL_OK {
+            return Err(Error::new(errcode));
+        }
+    }
+    let mut transid: u64 = 0;
+    let errcode = unsafe { ffi::btrfs_util_start_sync_fd(raw_fd, &mut transid) };
+    if errcode == ffi::btrfs_util_error::BTRFS_UTIL_OK {
+        Ok(transid)
+    } else {
+        Err(Error::new(errcode))
+    }
+}
+
+/// Forces the filesystem containing `path` to commit the transaction it is currently in, without
+/// waiting for the commit to finish.
+///
+/// Returns the transaction ID, which can be passed to [`wait_sync`] to block until it commits.
+pub fn start_sync<P: AsRef<Path>>(path: P) -> Result<u64, Error> {
+    let cpath = CString::new(path.as_ref().as_os_str().as_bytes()).unwrap();
+    let mut transid: u64 = 0;
+    let errcode = unsafe { ffi::btrfs_util_start_sync(cpath.as_ptr(), &mut transid) };
+    if errcode == ffi::btrfs_util_error::BTRFS_UTIL_OK {
+        Ok(transid)
+    } else {
+        Err(Error::new(errcode))
+    }
+}
+
+/// Like [`start_sync`], but takes an already-open file descriptor instead of a path.
+pub fn start_sync_fd<F: AsRawFd>(fd: &F) -> Result<u64, Error> {
+    let mut transid: u64 = 0;
+    let errcode = unsafe { ffi::btrfs_util_start_sync_fd(fd.as_raw_fd(), &mut transid) };
+    if errcode == ffi::btrfs_util_error::BTRFS_UTIL_OK {
+        Ok(transid)
+    } else {
+        Err(Error::new(errcode))
+    }
+}
+
+/// Waits for the transaction with the given ID on the filesystem containing `path` to commit.
+///
+/// `transid` of `0` waits for the currently running transaction instead of a specific one.
+pub fn wait_sync<P: AsRef<Path>>(path: P, transid: u64) -> Result<(), Error> {
+    let cpath = CString::new(path.as_ref().as_os_str().as_bytes()).unwrap();
+    let errcode = unsafe { ffi::btrfs_util_wait_sync(cpath.as_ptr(), transid) };
+    if errcode == ffi::btrfs_util_error::BTRFS_UTIL_OK {
+        Ok(())
+    } else {
+        Err(Error::new(errcode))
+    }
+}
+
+/// Like [`wait_sync`], but takes an already-open file descriptor instead of a path.
+///
+/// `transid` of `0` waits for the currently running transaction instead of a specific one.
+pub fn wait_sync_fd<F: AsRawFd>(fd: &F, transid: u64) -> Result<(), Error> {
+    let errcode = unsafe { ffi::btrfs_util_wait_sync_fd(fd.as_raw_fd(), transid) };
+    if errcode == ffi::btrfs_util_error::BTRFS_UTIL_OK {
+        Ok(())
+    } else {
+        Err(Error::new(errcode))
+    }
+}
+
 bitflags! {
     #[derive(Default)]
     pub struct CreateSubvolumeFlags: c_int {}
@@ -146,6 +453,96 @@ pub fn create_subvolume<P: AsRef<Path>>(
     }
 }
 
+/// Like [`create_subvolume`], but takes a file descriptor for the parent directory and the new
+/// subvolume's `name` within it, instead of a path.
+pub fn create_subvolume_fd<F: AsRawFd>(
+    parent_fd: &F,
+    name: &str,
+    flags: CreateSubvolumeFlags,
+    qgroup: Option<QgroupInherit>,
+) -> Result<(), Error> {
+    let cname = CString::new(name).unwrap();
+    let cflags = flags.bits();
+    let cqgroup: *mut ffi::btrfs_util_qgroup_inherit = if let Some(qg) = qgroup {
+        qg.as_ptr()
+    } else {
+        std::ptr::null_mut()
+    };
+    let errcode = unsafe {
+        ffi::btrfs_util_create_subvolume_fd(
+            parent_fd.as_raw_fd(),
+            cname.as_ptr(),
+            cflags,
+            std::ptr::null_mut(),
+            cqgroup,
+        )
+    };
+    if errcode != ffi::btrfs_util_error::BTRFS_UTIL_OK {
+        Err(Error::new(errcode))
+    } else {
+        Ok(())
+    }
+}
+
+/// Creates a new subvolume without waiting for the creation to be committed to disk.
+///
+/// Returns the transaction ID of the create operation; pass it to [`wait_sync`] to block until
+/// the subvolume has been committed.
+pub fn create_subvolume_async<P: AsRef<Path>>(
+    path: P,
+    flags: CreateSubvolumeFlags,
+    qgroup: Option<QgroupInherit>,
+) -> Result<u64, Error> {
+    let cpath = CString::new(path.as_ref().as_os_str().as_bytes()).unwrap();
+    let cflags = flags.bits();
+    let cqgroup: *mut ffi::btrfs_util_qgroup_inherit = if let Some(qg) = qgroup {
+        qg.as_ptr()
+    } else {
+        std::ptr::null_mut()
+    };
+    let mut async_transid: u64 = 0;
+    let errcode = unsafe {
+        ffi::btrfs_util_create_subvolume(cpath.as_ptr(), cflags, &mut async_transid, cqgroup)
+    };
+    if errcode != ffi::btrfs_util_error::BTRFS_UTIL_OK {
+        Err(Error::new(errcode))
+    } else {
+        Ok(async_transid)
+    }
+}
+
+/// Like [`create_subvolume_async`], but takes a file descriptor for the parent directory and the
+/// new subvolume's `name` within it, instead of a path.
+pub fn create_subvolume_async_fd<F: AsRawFd>(
+    parent_fd: &F,
+    name: &str,
+    flags: CreateSubvolumeFlags,
+    qgroup: Option<QgroupInherit>,
+) -> Result<u64, Error> {
+    let cname = CString::new(name).unwrap();
+    let cflags = flags.bits();
+    let cqgroup: *mut ffi::btrfs_util_qgroup_inherit = if let Some(qg) = qgroup {
+        qg.as_ptr()
+    } else {
+        std::ptr::null_mut()
+    };
+    let mut async_transid: u64 = 0;
+    let errcode = unsafe {
+        ffi::btrfs_util_create_subvolume_fd(
+            parent_fd.as_raw_fd(),
+            cname.as_ptr(),
+            cflags,
+            &mut async_transid,
+            cqgroup,
+        )
+    };
+    if errcode != ffi::btrfs_util_error::BTRFS_UTIL_OK {
+        Err(Error::new(errcode))
+    } else {
+        Ok(async_transid)
+    }
+}
+
 bitflags! {
     #[derive(Default)]
     pub struct CreateSnapshotFlags: c_int {
@@ -184,3 +581,101 @@ pub fn create_snapshot<P: AsRef<Path>, Q: AsRef<Path>>(
     }
     Ok(())
 }
+
+/// Like [`create_snapshot`], but takes a file descriptor for the source subvolume instead of a
+/// path.
+pub fn create_snapshot_fd<F: AsRawFd, Q: AsRef<Path>>(
+    source_fd: &F,
+    path: Q,
+    flags: CreateSnapshotFlags,
+    qgroup: Option<QgroupInherit>,
+) -> Result<(), Error> {
+    let cpath = CString::new(path.as_ref().as_os_str().as_bytes()).unwrap();
+    let cflags = flags.bits();
+    let cqgroup: *mut ffi::btrfs_util_qgroup_inherit = if let Some(qg) = qgroup {
+        qg.as_ptr()
+    } else {
+        std::ptr::null_mut()
+    };
+    unsafe {
+        let errcode = ffi::btrfs_util_create_snapshot_fd(
+            source_fd.as_raw_fd(),
+            cpath.as_ptr(),
+            cflags,
+            std::ptr::null_mut(),
+            cqgroup,
+        );
+        if errcode != ffi::btrfs_util_error::BTRFS_UTIL_OK {
+            return Err(Error::new(errcode));
+        }
+    }
+    Ok(())
+}
+
+/// Creates a new snapshot from a source subvolume without waiting for the creation to be
+/// committed to disk.
+///
+/// Returns the transaction ID of the create operation; pass it to [`wait_sync`] to block until
+/// the snapshot has been committed. Combined with [`delete_subvolume_async`], this lets a
+/// snapshot-rotation tool fire off many operations and block on a single [`wait_sync`] at the
+/// end, rather than waiting for a sync after each operation.
+pub fn create_snapshot_async<P: AsRef<Path>, Q: AsRef<Path>>(
+    source: P,
+    path: Q,
+    flags: CreateSnapshotFlags,
+    qgroup: Option<QgroupInherit>,
+) -> Result<u64, Error> {
+    let csource = CString::new(source.as_ref().as_os_str().as_bytes()).unwrap();
+    let cpath = CString::new(path.as_ref().as_os_str().as_bytes()).unwrap();
+    let cflags = flags.bits();
+    let cqgroup: *mut ffi::btrfs_util_qgroup_inherit = if let Some(qg) = qgroup {
+        qg.as_ptr()
+    } else {
+        std::ptr::null_mut()
+    };
+    let mut async_transid: u64 = 0;
+    unsafe {
+        let errcode = ffi::btrfs_util_create_snapshot(
+            csource.as_ptr(),
+            cpath.as_ptr(),
+            cflags,
+            &mut async_transid,
+            cqgroup,
+        );
+        if errcode != ffi::btrfs_util_error::BTRFS_UTIL_OK {
+            return Err(Error::new(errcode));
+        }
+    }
+    Ok(async_transid)
+}
+
+/// Like [`create_snapshot_async`], but takes a file descriptor for the source subvolume instead
+/// of a path.
+pub fn create_snapshot_async_fd<F: AsRawFd, Q: AsRef<Path>>(
+    source_fd: &F,
+    path: Q,
+    flags: CreateSnapshotFlags,
+    qgroup: Option<QgroupInherit>,
+) -> Result<u64, Error> {
+    let cpath = CString::new(path.as_ref().as_os_str().as_bytes()).unwrap();
+    let cflags = flags.bits();
+    let cqgroup: *mut ffi::btrfs_util_qgroup_inherit = if let Some(qg) = qgroup {
+        qg.as_ptr()
+    } else {
+        std::ptr::null_mut()
+    };
+    let mut async_transid: u64 = 0;
+    unsafe {
+        let errcode = ffi::btrfs_util_create_snapshot_fd(
+            source_fd.as_raw_fd(),
+            cpath.as_ptr(),
+            cflags,
+            &mut async_transid,
+            cqgroup,
+        );
+        if errcode != ffi::btrfs_util_error::BTRFS_UTIL_OK {
+            return Err(Error::new(errcode));
+        }
+    }
+    Ok(async_transid)
+}
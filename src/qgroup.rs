@@ -0,0 +1,56 @@
+use crate::Error;
+use std::ptr;
+
+/// Specifies which qgroups a new subvolume or snapshot should inherit limits from.
+///
+/// Built up with [`QgroupInherit::add_group`] and passed to [`crate::create_subvolume`] or
+/// [`crate::create_snapshot`].
+pub struct QgroupInherit {
+    ptr: *mut ffi::btrfs_util_qgroup_inherit,
+}
+
+impl QgroupInherit {
+    /// Creates an empty qgroup inheritance specification.
+    pub fn new() -> Result<Self, Error> {
+        let mut ptr: *mut ffi::btrfs_util_qgroup_inherit = ptr::null_mut();
+        let errcode = unsafe { ffi::btrfs_util_qgroup_inherit_create(&mut ptr) };
+        if errcode == ffi::btrfs_util_error::BTRFS_UTIL_OK {
+            Ok(QgroupInherit { ptr })
+        } else {
+            Err(Error::new(errcode))
+        }
+    }
+
+    /// Adds a qgroup to inherit from.
+    pub fn add_group(&mut self, qgroupid: u64) -> Result<(), Error> {
+        let errcode = unsafe { ffi::btrfs_util_qgroup_inherit_add_group(&mut self.ptr, qgroupid) };
+        if errcode == ffi::btrfs_util_error::BTRFS_UTIL_OK {
+            Ok(())
+        } else {
+            Err(Error::new(errcode))
+        }
+    }
+
+    /// Returns the qgroup IDs this specification will inherit from.
+    pub fn groups(&self) -> &[u64] {
+        let mut groups: *const u64 = ptr::null();
+        let mut n: usize = 0;
+        unsafe {
+            ffi::btrfs_util_qgroup_inherit_get_groups(self.ptr, &mut groups, &mut n);
+        }
+        if n == 0 {
+            return &[];
+        }
+        unsafe { std::slice::from_raw_parts(groups, n) }
+    }
+
+    pub(crate) fn as_ptr(&self) -> *mut ffi::btrfs_util_qgroup_inherit {
+        self.ptr
+    }
+}
+
+impl Drop for QgroupInherit {
+    fn drop(&mut self) {
+        unsafe { ffi::btrfs_util_qgroup_inherit_destroy(self.ptr) };
+    }
+}
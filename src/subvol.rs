@@ -0,0 +1,157 @@
+use crate::Error;
+use bitflags::bitflags;
+use std::{
+    ffi::{CStr, CString},
+    os::{
+        raw::c_int,
+        unix::prelude::{AsRawFd, OsStrExt, OsStringExt},
+    },
+    path::{Path, PathBuf},
+    ptr,
+};
+
+/// Information about a subvolume, as returned by [`crate::subvolume_info`] and
+/// [`crate::subvolume_info_with_id`].
+pub struct SubvolumeInfo(ffi::btrfs_util_subvolume_info);
+
+impl SubvolumeInfo {
+    pub(crate) fn new() -> Self {
+        SubvolumeInfo(unsafe { std::mem::zeroed() })
+    }
+
+    pub(crate) fn as_ptr(&mut self) -> *mut ffi::btrfs_util_subvolume_info {
+        &mut self.0
+    }
+
+    /// The ID of this subvolume.
+    pub fn id(&self) -> u64 {
+        self.0.id
+    }
+
+    /// The ID of this subvolume's parent, or `0` if this is the root subvolume.
+    pub fn parent_id(&self) -> u64 {
+        self.0.parent_id
+    }
+
+    /// The inode number of the directory containing this subvolume in its parent.
+    pub fn dir_id(&self) -> u64 {
+        self.0.dir_id
+    }
+
+    /// The subvolume flags, e.g. whether it is read-only.
+    pub fn flags(&self) -> u64 {
+        self.0.flags
+    }
+
+    /// The UUID of this subvolume.
+    pub fn uuid(&self) -> [u8; 16] {
+        self.0.uuid
+    }
+
+    /// The UUID of the subvolume this one was created from, if any.
+    pub fn parent_uuid(&self) -> [u8; 16] {
+        self.0.parent_uuid
+    }
+
+    /// The UUID this subvolume was received with, if it was received from a send stream.
+    pub fn received_uuid(&self) -> [u8; 16] {
+        self.0.received_uuid
+    }
+
+    /// The transaction ID in which this subvolume was created.
+    pub fn generation(&self) -> u64 {
+        self.0.generation
+    }
+}
+
+bitflags! {
+    #[derive(Default)]
+    pub struct SubvolumeIteratorFlags: c_int {
+        const POST_ORDER = ffi::BTRFS_UTIL_SUBVOLUME_ITERATOR_POST_ORDER as c_int;
+    }
+}
+
+/// Iterates over the subvolumes beneath a path, as created by [`crate::subvolume_iterator`].
+///
+/// Yields the path of each subvolume, relative to the root the iterator was created with, and its
+/// ID. Callers that need an absolute path must join the yielded path onto their own base path.
+pub struct SubvolumeIterator {
+    ptr: *mut ffi::btrfs_util_subvolume_iterator,
+}
+
+impl Iterator for SubvolumeIterator {
+    type Item = Result<(PathBuf, u64), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut cpath: *mut std::os::raw::c_char = ptr::null_mut();
+        let mut id: u64 = 0;
+        let errcode =
+            unsafe { ffi::btrfs_util_subvolume_iterator_next(self.ptr, &mut cpath, &mut id) };
+        match errcode {
+            ffi::btrfs_util_error::BTRFS_UTIL_OK => {
+                let path = unsafe {
+                    let bytes = CStr::from_ptr(cpath).to_bytes().to_vec();
+                    libc::free(cpath as *mut _);
+                    PathBuf::from(std::ffi::OsString::from_vec(bytes))
+                };
+                Some(Ok((path, id)))
+            }
+            ffi::btrfs_util_error::BTRFS_UTIL_ERROR_STOP_ITERATION => None,
+            _ => Some(Err(Error::new(errcode))),
+        }
+    }
+}
+
+impl Drop for SubvolumeIterator {
+    fn drop(&mut self) {
+        unsafe { ffi::btrfs_util_destroy_subvolume_iterator(self.ptr) };
+    }
+}
+
+/// Returns an iterator over the subvolumes beneath `path`.
+///
+/// `top` is the ID of the subvolume to iterate relative to, or `0` for the subvolume containing
+/// `path`. `flags` controls traversal order, e.g. [`SubvolumeIteratorFlags::POST_ORDER`] to visit
+/// children before their parents, which is useful when the caller intends to delete the whole
+/// tree bottom-up.
+///
+/// This requires appropriate privilege (`CAP_SYS_ADMIN`) unless `top` is `0`, in which case the
+/// kernel falls back to `BTRFS_IOC_GET_SUBVOL_ROOTREF` and `BTRFS_IOC_INO_LOOKUP_USER` (kernel >=
+/// 4.18) to enumerate subvolumes without it. In that unprivileged mode, subvolumes the caller
+/// cannot access (wrong permissions, or hidden behind another mount) are silently skipped rather
+/// than causing the walk to fail; see [`Error::GET_SUBVOL_ROOTREF_FAILED`],
+/// [`Error::INO_LOOKUP_USER_FAILED`] and [`Error::FS_INFO_FAILED`] for the failure modes that can
+/// still surface, e.g. if the filesystem itself cannot be queried.
+pub fn subvolume_iterator<P: AsRef<Path>>(
+    path: P,
+    top: u64,
+    flags: SubvolumeIteratorFlags,
+) -> Result<SubvolumeIterator, Error> {
+    let cpath = CString::new(path.as_ref().as_os_str().as_bytes()).unwrap();
+    let mut ptr: *mut ffi::btrfs_util_subvolume_iterator = ptr::null_mut();
+    let errcode = unsafe {
+        ffi::btrfs_util_create_subvolume_iterator(cpath.as_ptr(), top, flags.bits(), &mut ptr)
+    };
+    if errcode == ffi::btrfs_util_error::BTRFS_UTIL_OK {
+        Ok(SubvolumeIterator { ptr })
+    } else {
+        Err(Error::new(errcode))
+    }
+}
+
+/// Like [`subvolume_iterator`], but takes an already-open file descriptor instead of a path.
+pub fn subvolume_iterator_fd<F: AsRawFd>(
+    fd: &F,
+    top: u64,
+    flags: SubvolumeIteratorFlags,
+) -> Result<SubvolumeIterator, Error> {
+    let mut ptr: *mut ffi::btrfs_util_subvolume_iterator = ptr::null_mut();
+    let errcode = unsafe {
+        ffi::btrfs_util_create_subvolume_iterator_fd(fd.as_raw_fd(), top, flags.bits(), &mut ptr)
+    };
+    if errcode == ffi::btrfs_util_error::BTRFS_UTIL_OK {
+        Ok(SubvolumeIterator { ptr })
+    } else {
+        Err(Error::new(errcode))
+    }
+}